@@ -1,4 +1,8 @@
-use std::{collections::BTreeMap, ops::Add};
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, BTreeSet, BinaryHeap},
+    ops::Add,
+};
 
 pub trait Edge {
     type Cost: Default + Ord + Add<Self::Cost, Output = Self::Cost> + Clone;
@@ -7,27 +11,54 @@ pub trait Edge {
     fn cost(&self, context: &Self::Context) -> Self::Cost;
 }
 
-pub struct ShortestPath<Node, Edge> {
-    prev_map: BTreeMap<Node, (Node, Edge)>,
+pub struct ShortestPath<Node, Edge, Cost> {
+    prev_map: BTreeMap<Node, Vec<(Node, Edge)>>,
+    distance: BTreeMap<Node, Cost>,
 }
 
-impl<Node, Edge> ShortestPath<Node, Edge>
+impl<Node, Edge, Cost> ShortestPath<Node, Edge, Cost>
 where
     Node: Eq + Ord + Clone,
     Edge: Clone,
+    Cost: Clone,
 {
-    fn new(prev_map: BTreeMap<Node, (Node, Edge)>) -> Self {
+    fn new(prev_map: BTreeMap<Node, Vec<(Node, Edge)>>, distance: BTreeMap<Node, Cost>) -> Self {
         ShortestPath {
             prev_map: prev_map,
+            distance: distance,
         }
     }
 
+    /// one of the (possibly several) tied minimum-cost predecessors of `node`
     pub fn prev(&self, node: &Node) -> Option<(Node, Edge)> {
-        self.prev_map.get(node).map(Clone::clone)
+        self.prev_map.get(node).and_then(|preds| preds.first()).map(Clone::clone)
     }
 
-    /// reverse sequence from goal to start
-    /// include node with corresponding edge does not include goal
+    /// total cost from the search's start node to `node`, if reached
+    pub fn cost(&self, node: &Node) -> Option<Cost> {
+        self.distance.get(node).map(Clone::clone)
+    }
+
+    /// every node reached by the search, in no particular guaranteed order
+    /// beyond `Node`'s own `Ord`
+    pub fn reachable(&self) -> impl Iterator<Item = &Node> {
+        self.distance.keys()
+    }
+
+    /// the full start-to-node cost map computed by the search; turns a
+    /// single run into an answer for every one-to-all query without
+    /// rerunning the search per destination
+    pub fn distances(&self) -> &BTreeMap<Node, Cost> {
+        &self.distance
+    }
+
+    /// all tied minimum-cost predecessors of `node`
+    pub fn all_prev(&self, node: &Node) -> &[(Node, Edge)] {
+        self.prev_map.get(node).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// reverse sequence from goal to start, picking one predecessor at each
+    /// tie; include node with corresponding edge does not include goal
     pub fn sequence(self, start: Node, goal: Node) -> Vec<(Node, Edge)> {
         let mut sequence = Vec::new();
 
@@ -48,6 +79,45 @@ where
 
         sequence
     }
+
+    /// every minimum-cost path from `start` to `goal`, reverse order like
+    /// `sequence`; stops collecting once `limit` paths have been found so a
+    /// graph with many ties cannot blow up the caller
+    ///
+    /// also tracks the nodes already walked on the current path and refuses
+    /// to step back onto one of them: zero-cost edges can make `all_prev`
+    /// tie two nodes on each other, and without this guard that loop would
+    /// be walked forever instead of being capped by `limit`
+    pub fn all_sequences(&self, start: Node, goal: Node, limit: usize) -> Vec<Vec<(Node, Edge)>> {
+        let mut result = Vec::new();
+        let mut seen = BTreeSet::new();
+        seen.insert(goal.clone());
+        let mut stack = vec![(goal, Vec::new(), seen)];
+
+        while let Some((this, sequence, seen)) = stack.pop() {
+            if result.len() >= limit {
+                break
+            }
+
+            if this == start {
+                result.push(sequence);
+                continue
+            }
+
+            for prev in self.all_prev(&this) {
+                if seen.contains(&prev.0) {
+                    continue
+                }
+                let mut sequence = sequence.clone();
+                sequence.push(prev.clone());
+                let mut seen = seen.clone();
+                seen.insert(prev.0.clone());
+                stack.push((prev.0.clone(), sequence, seen));
+            }
+        }
+
+        result
+    }
 }
 
 pub trait Graph
@@ -60,36 +130,277 @@ where
 
     fn neighbors(&self, node: Self::Node) -> Vec<(Self::Node, Self::Edge)>;
 
-    fn shortest_path(&self, context: &Self::Context, start: Self::Node) -> ShortestPath<Self::Node, Self::Edge> {
+    /// lower-bound estimate of the remaining cost from `node` to `goal`,
+    /// used to order the frontier in `astar`; defaults to zero so plain
+    /// Dijkstra callers are unaffected
+    ///
+    /// must never overestimate the true remaining cost (admissible), or the
+    /// result is no longer guaranteed optimal. Consistency (the stronger
+    /// `h(n) <= edge_cost(n, n') + h(n')` for every edge) is *not* required:
+    /// `search`/`search_masked` reopen a node whenever a strictly cheaper
+    /// cost-so-far is found for it, so a merely admissible heuristic still
+    /// yields an optimal path, just at the cost of possibly re-expanding
+    /// some nodes more than once
+    fn heuristic(&self, node: &Self::Node, goal: &Self::Node) -> <Self::Edge as Edge>::Cost {
+        let _ = (node, goal);
+        Default::default()
+    }
+
+    /// shared heap-based core for `shortest_path`, `shortest_path_to` and
+    /// `astar`; stops early as soon as `goal` is popped off the frontier
+    /// finalized. `priority` orders the frontier on top of the true
+    /// cost-so-far tracked in `distance` (plain `g` for Dijkstra, `g + h`
+    /// for A*)
+    fn search(
+        &self,
+        context: &Self::Context,
+        start: Self::Node,
+        goal: Option<&Self::Node>,
+        priority: impl Fn(&Self::Node, &<Self::Edge as Edge>::Cost) -> <Self::Edge as Edge>::Cost,
+    ) -> ShortestPath<Self::Node, Self::Edge, <Self::Edge as Edge>::Cost> {
+        let no_nodes = BTreeSet::new();
+        let no_edges = BTreeSet::new();
+        self.search_masked(context, start, goal, priority, &no_nodes, &no_edges)
+    }
+
+    /// like `search`, but `banned_nodes` and `banned_edges` are consulted
+    /// while relaxing neighbors so `k_shortest_paths` can explore the graph
+    /// with parts masked out without mutating the caller's `Graph`
+    fn search_masked(
+        &self,
+        context: &Self::Context,
+        start: Self::Node,
+        goal: Option<&Self::Node>,
+        priority: impl Fn(&Self::Node, &<Self::Edge as Edge>::Cost) -> <Self::Edge as Edge>::Cost,
+        banned_nodes: &BTreeSet<Self::Node>,
+        banned_edges: &BTreeSet<(Self::Node, Self::Node)>,
+    ) -> ShortestPath<Self::Node, Self::Edge, <Self::Edge as Edge>::Cost> {
         let mut distance: BTreeMap<Self::Node, <Self::Edge as Edge>::Cost> = BTreeMap::new();
-        let mut prev = BTreeMap::new();
-        distance.insert(start, Default::default());
+        let mut prev: BTreeMap<Self::Node, Vec<(Self::Node, Self::Edge)>> = BTreeMap::new();
+        let start_cost = <Self::Edge as Edge>::Cost::default();
+        distance.insert(start.clone(), start_cost.clone());
 
-        let mut visited = BTreeMap::new();
-        loop {
-            let maybe = distance
-                .iter()
-                .filter(|&(n, _)| visited.get(n).is_none())
-                .min_by(|&(_, left), &(_, right)| left.cmp(right))
-                .map(|(n, cost)| (n.clone(), cost.clone()));
-            let (min, min_cost) = match maybe {
-                Some(m) => m,
-                None => break,
-            };
+        // cost-so-far a node was last expanded at; a node is reopened (and
+        // its neighbors relaxed again) whenever popped with a strictly
+        // cheaper cost than this, which is what keeps an admissible but
+        // inconsistent heuristic from finalizing a node too early
+        let mut expanded_at: BTreeMap<Self::Node, <Self::Edge as Edge>::Cost> = BTreeMap::new();
+        let mut heap = BinaryHeap::new();
+        heap.push(Reverse((priority(&start, &start_cost), start_cost, start)));
 
-            visited.insert(min.clone(), ());
+        while let Some(Reverse((_, entry_cost, min))) = heap.pop() {
+            match distance.get(&min) {
+                Some(current) if *current == entry_cost => {},
+                _ => continue, // stale: a cheaper cost for `min` was found after this entry was pushed
+            }
+            if expanded_at.get(&min).map_or(false, |best| *best <= entry_cost) {
+                continue
+            }
+            expanded_at.insert(min.clone(), entry_cost.clone());
+
+            if goal == Some(&min) {
+                break
+            }
 
             for (this, edge) in self.neighbors(min.clone()) {
-                let alt = min_cost.clone() + edge.cost(context);
-                let this_distance = distance.get(&this);
-                if this_distance.is_none() || this_distance.unwrap().clone() >= alt {
-                    distance.insert(this.clone(), alt);
-                    prev.insert(this.clone(), (min.clone(), edge));
+                if banned_nodes.contains(&this) || banned_edges.contains(&(min.clone(), this.clone())) {
+                    continue
+                }
+
+                let alt = entry_cost.clone() + edge.cost(context);
+                match distance.get(&this) {
+                    None => {
+                        distance.insert(this.clone(), alt.clone());
+                        prev.insert(this.clone(), vec![(min.clone(), edge)]);
+                        heap.push(Reverse((priority(&this, &alt), alt, this)));
+                    },
+                    Some(this_distance) if alt < *this_distance => {
+                        distance.insert(this.clone(), alt.clone());
+                        prev.insert(this.clone(), vec![(min.clone(), edge)]);
+                        heap.push(Reverse((priority(&this, &alt), alt, this)));
+                    },
+                    Some(this_distance) if alt == *this_distance => {
+                        prev.entry(this.clone()).or_insert_with(Vec::new).push((min.clone(), edge));
+                    },
+                    Some(_) => (),
+                }
+            }
+        }
+
+        ShortestPath::new(prev, distance)
+    }
+
+    fn shortest_path(&self, context: &Self::Context, start: Self::Node) -> ShortestPath<Self::Node, Self::Edge, <Self::Edge as Edge>::Cost> {
+        self.search(context, start, None, |_, cost| cost.clone())
+    }
+
+    /// like `shortest_path`, but stops the search as soon as `goal` is
+    /// finalized instead of exhausting the whole reachable component
+    fn shortest_path_to(
+        &self,
+        context: &Self::Context,
+        start: Self::Node,
+        goal: Self::Node,
+    ) -> ShortestPath<Self::Node, Self::Edge, <Self::Edge as Edge>::Cost> {
+        self.search(context, start, Some(&goal), |_, cost| cost.clone())
+    }
+
+    /// A*: like `shortest_path_to`, but orders the frontier by `g + heuristic`
+    /// instead of `g` alone, which can reach `goal` while relaxing far fewer
+    /// nodes. Requires `heuristic` to be admissible (never overestimate) to
+    /// stay optimal
+    fn astar(
+        &self,
+        context: &Self::Context,
+        start: Self::Node,
+        goal: Self::Node,
+    ) -> ShortestPath<Self::Node, Self::Edge, <Self::Edge as Edge>::Cost> {
+        self.search(context, start, Some(&goal), |node, cost| {
+            cost.clone() + self.heuristic(node, &goal)
+        })
+    }
+
+    /// Yen's algorithm: the `k` best loopless paths from `start` to `goal`,
+    /// cheapest first. `accepted` holds the paths accepted so far (starting with the
+    /// plain shortest path); each round tries every node of the last
+    /// accepted path as a "spur node", bans the edges/nodes that would just
+    /// retrace an already-found path out of its root, and reruns a masked
+    /// single-pair search from the spur to `goal` to produce a fresh
+    /// candidate. The cheapest not-yet-accepted candidate becomes the next
+    /// entry in `accepted`; fewer than `k` are returned if the graph runs out of
+    /// loopless alternatives
+    fn k_shortest_paths(
+        &self,
+        context: &Self::Context,
+        start: Self::Node,
+        goal: Self::Node,
+        k: usize,
+    ) -> Vec<(<Self::Edge as Edge>::Cost, Vec<(Self::Node, Self::Edge)>)> {
+        let path_cost = |path: &[(Self::Node, Self::Edge)]| {
+            path.iter().fold(<Self::Edge as Edge>::Cost::default(), |acc, (_, edge)| acc + edge.cost(context))
+        };
+        let nodes_of = |path: &[(Self::Node, Self::Edge)]| path.iter().map(|(n, _)| n.clone()).collect::<Vec<_>>();
+
+        if k == 0 {
+            return Vec::new()
+        }
+
+        let mut first = self.shortest_path_to(context, start.clone(), goal.clone()).sequence(start.clone(), goal.clone());
+        first.reverse();
+        if first.is_empty() && start != goal {
+            return Vec::new()
+        }
+
+        let mut accepted = vec![(path_cost(&first), first)];
+        let mut accepted_nodes = vec![nodes_of(&accepted[0].1)];
+        let mut candidates: Vec<(<Self::Edge as Edge>::Cost, Vec<(Self::Node, Self::Edge)>)> = Vec::new();
+        let mut candidate_nodes: Vec<Vec<Self::Node>> = Vec::new();
+
+        while accepted.len() < k {
+            let last_path = accepted.last().expect("seeded with the first shortest path").1.clone();
+
+            for i in 0..last_path.len() {
+                let spur_node = last_path[i].0.clone();
+                let root_path = last_path[..i].to_vec();
+                let root_nodes = nodes_of(&root_path);
+
+                let mut banned_nodes = BTreeSet::new();
+                for node in &root_nodes {
+                    banned_nodes.insert(node.clone());
                 }
+
+                let root_through_spur = nodes_of(&last_path[..i + 1]);
+                let mut banned_edges = BTreeSet::new();
+                for path in accepted.iter().map(|(_, p)| p).chain(candidates.iter().map(|(_, p)| p)) {
+                    if path.len() > i && nodes_of(&path[..i + 1]) == root_through_spur {
+                        let next = if i + 1 < path.len() { path[i + 1].0.clone() } else { goal.clone() };
+                        banned_edges.insert((path[i].0.clone(), next));
+                    }
+                }
+
+                let mut spur_path = self
+                    .search_masked(context, spur_node.clone(), Some(&goal), |_, cost| cost.clone(), &banned_nodes, &banned_edges)
+                    .sequence(spur_node.clone(), goal.clone());
+                spur_path.reverse();
+
+                if spur_path.is_empty() && spur_node != goal {
+                    continue
+                }
+
+                let mut total_path = root_path;
+                total_path.extend(spur_path);
+                let total_nodes = nodes_of(&total_path);
+
+                if accepted_nodes.contains(&total_nodes) || candidate_nodes.contains(&total_nodes) {
+                    continue
+                }
+
+                candidate_nodes.push(total_nodes);
+                candidates.push((path_cost(&total_path), total_path));
             }
+
+            let cheapest = candidates
+                .iter()
+                .enumerate()
+                .min_by(|(_, (left, _)), (_, (right, _))| left.cmp(right))
+                .map(|(index, _)| index);
+            let index = match cheapest {
+                Some(index) => index,
+                None => break,
+            };
+
+            candidate_nodes.remove(index);
+            let chosen = candidates.remove(index);
+            accepted_nodes.push(nodes_of(&chosen.1));
+            accepted.push(chosen);
         }
 
-        ShortestPath::new(prev)
+        accepted
+    }
+
+    /// closeness centrality of each of `nodes`: run a single-source search
+    /// from the node, sum the finalized distances to every reachable node,
+    /// and report `(reachable - 1) / sum`. Pass `normalization` as
+    /// `Some(total_nodes)` (the node count of the whole graph, not just the
+    /// reachable component) to instead apply the Wasserman-Faust correction
+    /// `((reachable-1)/(total_nodes-1)) * ((reachable-1)/sum)`, which keeps
+    /// centrality comparable across disconnected components.
+    ///
+    /// `neighbors` is the only adjacency this trait exposes, with no way to
+    /// walk edges backward, so this always measures out-closeness (distance
+    /// from the node, not to it). On an undirected graph, where every edge
+    /// is its own reverse, out-closeness and in-closeness coincide and the
+    /// result is the usual closeness centrality; on a directed graph it is
+    /// only the out-closeness variant.
+    fn closeness_centrality(
+        &self,
+        context: &Self::Context,
+        nodes: impl IntoIterator<Item = Self::Node>,
+        normalization: Option<usize>,
+    ) -> BTreeMap<Self::Node, f64>
+    where
+        <Self::Edge as Edge>::Cost: Into<f64>,
+    {
+        nodes
+            .into_iter()
+            .map(|node| {
+                let path = self.shortest_path(context, node.clone());
+                let reachable = path.distances().len();
+                let sum: f64 = path.distances().values().cloned().map(Into::into).sum();
+
+                let centrality = if reachable <= 1 || sum == 0.0 {
+                    0.0
+                } else {
+                    let reachable_others = (reachable - 1) as f64;
+                    match normalization {
+                        Some(total_nodes) => (reachable_others / (total_nodes as f64 - 1.0)) * (reachable_others / sum),
+                        None => reachable_others / sum,
+                    }
+                };
+
+                (node, centrality)
+            })
+            .collect()
     }
 }
 
@@ -191,4 +502,146 @@ mod test {
 
         assert_eq!(vec![3, 2, 1, 0], sequence);
     }
+
+    #[test]
+    fn test_shortest_path_to() {
+        let mut graph = GraphImpl::new(10);
+        graph.insert(0, 1, 10);
+        graph.insert(1, 9, 50);
+        graph.insert(1, 2, 10);
+        graph.insert(2, 3, 10);
+        graph.insert(3, 9, 10);
+
+        let path = graph.shortest_path_to(&(), graph.nodes[0].clone(), graph.nodes[9].clone());
+        let sequence = path.sequence(graph.nodes[0].clone(), graph.nodes[9].clone())
+            .into_iter()
+            .map(|(n, _)| n)
+            .collect::<Vec<_>>();
+
+        assert_eq!(vec![3, 2, 1, 0], sequence);
+    }
+
+    #[test]
+    fn test_astar_default_heuristic() {
+        let mut graph = GraphImpl::new(10);
+        graph.insert(0, 1, 10);
+        graph.insert(1, 9, 50);
+        graph.insert(1, 2, 10);
+        graph.insert(2, 3, 10);
+        graph.insert(3, 9, 10);
+
+        let path = graph.astar(&(), graph.nodes[0].clone(), graph.nodes[9].clone());
+        let sequence = path.sequence(graph.nodes[0].clone(), graph.nodes[9].clone())
+            .into_iter()
+            .map(|(n, _)| n)
+            .collect::<Vec<_>>();
+
+        assert_eq!(vec![3, 2, 1, 0], sequence);
+    }
+
+    #[test]
+    fn test_search_reopens_finalized_node_on_cheaper_cost() {
+        // node 1 is first reached directly at cost 5; a cheaper route via
+        // node 2 (cost 2) arrives only after node 1 has already been
+        // finalized by a priority function that rushes it to the front of
+        // the frontier. Without reopening, node 1's first (stale) expansion
+        // is the only one that ever reaches the goal, so the goal is
+        // reported at cost 6 instead of the true optimum of 3.
+        let mut graph = GraphImpl::new(4);
+        graph.insert(0, 1, 5);
+        graph.insert(0, 2, 1);
+        graph.insert(2, 1, 1);
+        graph.insert(1, 3, 1);
+
+        let rushed = graph.nodes[1];
+        let path = graph.search(&(), graph.nodes[0].clone(), Some(&graph.nodes[3].clone()), |node, cost| {
+            if *node == rushed { 0 } else { *cost }
+        });
+
+        assert_eq!(Some(3), path.cost(&graph.nodes[3]));
+    }
+
+    #[test]
+    fn test_all_sequences_tied() {
+        let mut graph = GraphImpl::new(4);
+        graph.insert(0, 1, 10);
+        graph.insert(0, 2, 10);
+        graph.insert(1, 3, 10);
+        graph.insert(2, 3, 10);
+
+        let path = graph.shortest_path(&(), graph.nodes[0].clone());
+        let mut sequences = path
+            .all_sequences(graph.nodes[0].clone(), graph.nodes[3].clone(), 10)
+            .into_iter()
+            .map(|sequence| sequence.into_iter().map(|(n, _)| n).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        sequences.sort();
+
+        assert_eq!(vec![vec![1, 0], vec![2, 0]], sequences);
+    }
+
+    #[test]
+    fn test_all_sequences_terminates_on_zero_cost_cycle() {
+        // a chain of zero-cost edges ties every node on the chain back onto
+        // its predecessor, so `all_prev(1)` includes `2` in addition to `0`:
+        // walking that tie back through `2` and `1` again visits the same
+        // two nodes forever unless the walk itself refuses to revisit a node
+        // already on the current path.
+        let mut graph = GraphImpl::new(3);
+        graph.insert(0, 1, 0);
+        graph.insert(1, 2, 0);
+
+        let path = graph.shortest_path(&(), graph.nodes[0].clone());
+        let sequences = path
+            .all_sequences(graph.nodes[0].clone(), graph.nodes[2].clone(), 5)
+            .into_iter()
+            .map(|sequence| sequence.into_iter().map(|(n, _)| n).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+
+        assert_eq!(vec![vec![1, 0]], sequences);
+    }
+
+    #[test]
+    fn test_k_shortest_paths() {
+        let mut graph = GraphImpl::new(4);
+        graph.insert(0, 1, 1);
+        graph.insert(1, 3, 1);
+        graph.insert(0, 2, 1);
+        graph.insert(2, 3, 2);
+        graph.insert(0, 3, 5);
+
+        let paths = graph.k_shortest_paths(&(), graph.nodes[0].clone(), graph.nodes[3].clone(), 3);
+        let costs = paths.into_iter().map(|(cost, _)| cost).collect::<Vec<_>>();
+
+        assert_eq!(vec![2, 3, 5], costs);
+    }
+
+    #[test]
+    fn test_closeness_centrality() {
+        let mut graph = GraphImpl::new(3);
+        graph.insert(0, 1, 10);
+        graph.insert(1, 2, 10);
+
+        let centrality = graph.closeness_centrality(&(), graph.nodes.clone(), None);
+
+        // node 1 is in the middle, reachable from/to both others at a lower
+        // total cost, so it should score higher than either endpoint
+        assert!(centrality[&graph.nodes[1]] > centrality[&graph.nodes[0]]);
+        assert!(centrality[&graph.nodes[1]] > centrality[&graph.nodes[2]]);
+    }
+
+    #[test]
+    fn test_distances() {
+        let mut graph = GraphImpl::new(3);
+        graph.insert(0, 1, 10);
+        graph.insert(1, 2, 5);
+
+        let path = graph.shortest_path(&(), graph.nodes[0].clone());
+
+        assert_eq!(Some(0), path.cost(&graph.nodes[0]));
+        assert_eq!(Some(10), path.cost(&graph.nodes[1]));
+        assert_eq!(Some(15), path.cost(&graph.nodes[2]));
+        assert_eq!(3, path.reachable().count());
+        assert_eq!(3, path.distances().len());
+    }
 }